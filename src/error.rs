@@ -33,6 +33,8 @@ pub enum Error {
     InvalidStatusCode(StatusCode),
     #[error("Stream ended")]
     StreamEnded,
+    #[error("Last event timed out")]
+    StreamTimeout,
 }
 
 impl From<EventStreamError<ReqwestError>> for Error {
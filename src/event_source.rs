@@ -18,7 +18,12 @@ use futures_timer::Delay;
 use pin_project_lite::pin_project;
 use reqwest::header::{HeaderName, HeaderValue};
 use reqwest::{Error as ReqwestError, IntoUrl, RequestBuilder, Response, StatusCode};
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default for [`EventSource::set_reset_backoff_after`]
+const DEFAULT_RESET_BACKOFF_AFTER: Duration = Duration::from_secs(60);
 
 #[cfg(not(target_arch="wasm32"))]
 type ResponseFuture = BoxFuture<'static, Result<Response, ReqwestError>>;
@@ -31,6 +36,73 @@ type EventStream = BoxStream<'static, Result<MessageEvent, EventStreamError<Reqw
 type EventStream = LocalBoxStream<'static, Result<MessageEvent, EventStreamError<ReqwestError>>>;
 
 type BoxedRetry = Box<dyn RetryPolicy + Send + Unpin + 'static>;
+type BoxedEventFilter = Box<dyn Fn(&str) -> bool + Send + 'static>;
+type Comments = Arc<Mutex<VecDeque<String>>>;
+
+/// Passes chunks through unchanged while pushing any SSE comment line (`:`-prefixed) it sees
+/// onto a shared queue, since `eventsource_stream` consumes comment lines without surfacing them
+pin_project! {
+    struct CommentTap<S> {
+        #[pin]
+        inner: S,
+        partial_line: Vec<u8>,
+        comments: Comments,
+    }
+}
+
+impl<S> CommentTap<S> {
+    fn new(inner: S, comments: Comments) -> Self {
+        Self {
+            inner,
+            partial_line: Vec::new(),
+            comments,
+        }
+    }
+}
+
+/// Finds newline-terminated comment lines in `chunk`, carrying a trailing partial line over in
+/// `partial_line` for the next call
+fn scan_for_comments(partial_line: &mut Vec<u8>, comments: &Comments, chunk: &[u8]) {
+    let mut start = 0;
+    for (i, &byte) in chunk.iter().enumerate() {
+        if byte != b'\n' {
+            continue;
+        }
+        let mut line = std::mem::take(partial_line);
+        line.extend_from_slice(&chunk[start..i]);
+        start = i + 1;
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if line.first() != Some(&b':') {
+            continue;
+        }
+        let text = line[1..].strip_prefix(b" ").unwrap_or(&line[1..]);
+        if let Ok(text) = std::str::from_utf8(text) {
+            comments.lock().unwrap().push_back(text.to_owned());
+        }
+    }
+    partial_line.extend_from_slice(&chunk[start..]);
+}
+
+impl<S, B, E> Stream for CommentTap<S>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    type Item = Result<B, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        match this.inner.poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                scan_for_comments(this.partial_line, this.comments, chunk.as_ref());
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            other => other,
+        }
+    }
+}
 
 /// The ready state of an [`EventSource`]
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
@@ -56,10 +128,17 @@ pub struct EventSource {
     cur_stream: Option<EventStream>,
     #[pin]
     delay: Option<Delay>,
+    #[pin]
+    read_timeout: Option<Delay>,
     is_closed: bool,
     retry_policy: BoxedRetry,
     last_event_id: String,
-    last_retry: Option<(usize, Duration)>
+    last_retry: Option<(usize, Duration)>,
+    last_event_timeout: Option<Duration>,
+    connected_at: Option<Instant>,
+    reset_backoff_after: Duration,
+    event_filter: Option<BoxedEventFilter>,
+    comments: Comments
 }
 }
 
@@ -76,10 +155,16 @@ impl EventSource {
             next_response: Some(res_future),
             cur_stream: None,
             delay: None,
+            read_timeout: None,
             is_closed: false,
             retry_policy: Box::new(DEFAULT_RETRY),
             last_event_id: String::new(),
             last_retry: None,
+            last_event_timeout: None,
+            connected_at: None,
+            reset_backoff_after: DEFAULT_RESET_BACKOFF_AFTER,
+            event_filter: None,
+            comments: Arc::new(Mutex::new(VecDeque::new())),
         })
     }
 
@@ -98,6 +183,21 @@ impl EventSource {
         self.retry_policy = policy
     }
 
+    /// Reconnect if no event arrives within `timeout` while the connection is open
+    pub fn set_last_event_timeout(&mut self, timeout: Option<Duration>) {
+        self.last_event_timeout = timeout;
+    }
+
+    /// Set how long a connection must stay open before the backoff sequence resets
+    pub fn set_reset_backoff_after(&mut self, duration: Duration) {
+        self.reset_backoff_after = duration;
+    }
+
+    /// Only yield [`Event::Message`]s whose `event` field satisfies `filter`
+    pub fn set_event_filter(&mut self, filter: impl Fn(&str) -> bool + Send + 'static) {
+        self.event_filter = Some(Box::new(filter));
+    }
+
     /// Get the last event id
     pub fn last_event_id(&self) -> &str {
         &self.last_event_id
@@ -159,10 +259,13 @@ impl<'a> EventSourceProjection<'a> {
     }
 
     fn handle_response(&mut self, res: Response) {
-        self.last_retry.take();
-        let mut stream = res.bytes_stream().eventsource();
+        self.connected_at.replace(Instant::now());
+        let comments: Comments = Arc::new(Mutex::new(VecDeque::new()));
+        let mut stream = CommentTap::new(res.bytes_stream(), comments.clone()).eventsource();
         stream.set_last_event_id(self.last_event_id.clone());
         self.cur_stream.replace(Box::pin(stream));
+        *self.comments = comments;
+        self.arm_read_timeout();
     }
 
     fn handle_event(&mut self, event: &MessageEvent) {
@@ -170,10 +273,29 @@ impl<'a> EventSourceProjection<'a> {
         if let Some(duration) = event.retry {
             self.retry_policy.set_reconnection_time(duration)
         }
+        self.arm_read_timeout();
+    }
+
+    /// (Re)arm the idle-read timer for `last_event_timeout`, or disarm it if none is set
+    fn arm_read_timeout(&mut self) {
+        match *self.last_event_timeout {
+            Some(timeout) => {
+                self.read_timeout.replace(Delay::new(timeout));
+            }
+            None => {
+                self.read_timeout.take();
+            }
+        }
     }
 
     fn handle_error(&mut self, error: &Error) {
         self.clear_fetch();
+        self.read_timeout.take();
+        if let Some(connected_at) = self.connected_at.take() {
+            if connected_at.elapsed() >= *self.reset_backoff_after {
+                self.last_retry.take();
+            }
+        }
         if let Some(retry_delay) = self.retry_policy.retry(error, *self.last_retry) {
             let retry_num = self.last_retry.map(|retry| retry.0).unwrap_or(1);
             *self.last_retry = Some((retry_num, retry_delay));
@@ -191,6 +313,9 @@ pub enum Event {
     Open,
     /// The event fired when a [`MessageEvent`] is received
     Message(MessageEvent),
+    /// The text of an SSE comment line (`:`-prefixed), without the leading `:` and one optional
+    /// space. Servers commonly send these as keep-alives.
+    Comment(String),
 }
 
 impl From<MessageEvent> for Event {
@@ -244,14 +369,32 @@ impl Stream for EventSource {
             }
         }
 
-        match this
+        if let Some(read_timeout) = this.read_timeout.as_mut().as_pin_mut() {
+            if read_timeout.poll(cx).is_ready() {
+                let err = Error::StreamTimeout;
+                this.handle_error(&err);
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+
+        let poll_result = this
             .cur_stream
             .as_mut()
             .as_pin_mut()
             .unwrap()
             .as_mut()
-            .poll_next(cx)
-        {
+            .poll_next(cx);
+
+        // Discovered as a side effect of the `poll_next` call above; emit it ahead of whatever
+        // `cur_stream` produced this round, waking ourselves if that result still needs delivery.
+        if let Some(comment) = this.comments.lock().unwrap().pop_front() {
+            if !matches!(poll_result, Poll::Pending) {
+                cx.waker().wake_by_ref();
+            }
+            return Poll::Ready(Some(Ok(Event::Comment(comment))));
+        }
+
+        match poll_result {
             Poll::Ready(Some(Err(err))) => {
                 let err = err.into();
                 this.handle_error(&err);
@@ -259,7 +402,13 @@ impl Stream for EventSource {
             }
             Poll::Ready(Some(Ok(event))) => {
                 this.handle_event(&event);
-                Poll::Ready(Some(Ok(event.into())))
+                match this.event_filter {
+                    Some(filter) if !filter(&event.event) => {
+                        cx.waker().wake_by_ref();
+                        Poll::Pending
+                    }
+                    _ => Poll::Ready(Some(Ok(event.into()))),
+                }
             }
             Poll::Ready(None) => {
                 let err = Error::StreamEnded;
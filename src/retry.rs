@@ -1,5 +1,8 @@
 use crate::error::Error;
-use std::time::Duration;
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
 
 pub fn default_should_retry(error: &Error) -> bool {
     match error {
@@ -7,7 +10,7 @@ pub fn default_should_retry(error: &Error) -> bool {
         | Error::Parser(_)
         | Error::InvalidStatusCode(_)
         | Error::InvalidContentType(_) => false,
-        Error::Transport(_) | Error::StreamEnded => true,
+        Error::Transport(_) | Error::StreamEnded | Error::StreamTimeout => true,
     }
 }
 
@@ -15,12 +18,29 @@ pub trait RetryPolicy {
     fn retry(&self, error: &Error, last_retry: Option<(usize, Duration)>) -> Option<Duration>;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct ExponentialBackoff {
     pub start: Duration,
     pub factor: f64,
     pub max_duration: Duration,
     pub max_retries: Option<usize>,
+    /// Fraction of the computed delay to randomize away, in `[0.0, 1.0]`. `0.0` (the default) is no jitter.
+    pub jitter: f64,
+    rng: Cell<u64>,
+}
+
+// Re-seed `rng` on clone instead of copying state, so clones don't replay the same sequence.
+impl Clone for ExponentialBackoff {
+    fn clone(&self) -> Self {
+        Self {
+            start: self.start,
+            factor: self.factor,
+            max_duration: self.max_duration,
+            max_retries: self.max_retries,
+            jitter: self.jitter,
+            rng: Cell::new(0),
+        }
+    }
 }
 
 impl ExponentialBackoff {
@@ -29,14 +49,55 @@ impl ExponentialBackoff {
         factor: f64,
         max_duration: Duration,
         max_retries: Option<usize>,
+    ) -> Self {
+        Self::with_jitter(start, factor, max_duration, max_retries, 0.0)
+    }
+
+    pub const fn with_jitter(
+        start: Duration,
+        factor: f64,
+        max_duration: Duration,
+        max_retries: Option<usize>,
+        jitter: f64,
     ) -> Self {
         Self {
             start,
             factor,
             max_duration,
             max_retries,
+            jitter,
+            rng: Cell::new(0),
         }
     }
+
+    /// Draws a uniform sample in `[0.0, 1.0)` from a lazily-seeded xorshift64 generator.
+    fn next_rand_unit(&self) -> f64 {
+        let mut state = self.rng.get();
+        if state == 0 {
+            state = random_seed();
+        }
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        self.rng.set(state);
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn apply_jitter(&self, delay: Duration) -> Duration {
+        if self.jitter <= 0.0 {
+            return delay;
+        }
+        // Scale is in `(1.0 - jitter, 1.0]`, so this only ever shrinks `delay` towards zero.
+        let scale = 1.0 - self.jitter.min(1.0) * self.next_rand_unit();
+        delay.mul_f64(scale)
+    }
+}
+
+fn random_seed() -> u64 {
+    match RandomState::new().build_hasher().finish() {
+        0 => 0x9E3779B97F4A7C15,
+        seed => seed,
+    }
 }
 
 impl RetryPolicy for ExponentialBackoff {
@@ -44,15 +105,16 @@ impl RetryPolicy for ExponentialBackoff {
         if !default_should_retry(error) {
             return None;
         }
-        if let Some((retry_num, last_duration)) = last_retry {
+        let delay = if let Some((retry_num, last_duration)) = last_retry {
             if self.max_retries.is_none() || retry_num < self.max_retries.unwrap() {
-                Some(last_duration.mul_f64(self.factor).min(self.max_duration))
+                last_duration.mul_f64(self.factor).min(self.max_duration)
             } else {
-                None
+                return None;
             }
         } else {
-            Some(self.start)
-        }
+            self.start
+        };
+        Some(self.apply_jitter(delay))
     }
 }
 
@@ -94,5 +156,101 @@ impl RetryPolicy for Never {
     }
 }
 
+/// Wraps an inner [`RetryPolicy`] with a total wall-clock budget to give up after.
+#[derive(Debug, Clone)]
+pub struct Budgeted<P> {
+    inner: P,
+    budget: Duration,
+    deadline: Cell<Option<Instant>>,
+}
+
+impl<P> Budgeted<P> {
+    pub const fn new(inner: P, budget: Duration) -> Self {
+        Self {
+            inner,
+            budget,
+            deadline: Cell::new(None),
+        }
+    }
+}
+
+impl<P: RetryPolicy> RetryPolicy for Budgeted<P> {
+    fn retry(&self, error: &Error, last_retry: Option<(usize, Duration)>) -> Option<Duration> {
+        let delay = self.inner.retry(error, last_retry)?;
+        // Reset the deadline at the start of each new outage instead of the policy's lifetime.
+        if last_retry.is_none() {
+            self.deadline.set(None);
+        }
+        let deadline = match self.deadline.get() {
+            Some(deadline) => deadline,
+            None => {
+                let deadline = Instant::now() + self.budget;
+                self.deadline.set(Some(deadline));
+                deadline
+            }
+        };
+        if Instant::now() + delay > deadline {
+            None
+        } else {
+            Some(delay)
+        }
+    }
+}
+
 pub const DEFAULT_RETRY: ExponentialBackoff =
     ExponentialBackoff::new(Duration::from_millis(300), 2., Duration::from_secs(5), None);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_never_exceeds_nominal_delay() {
+        let policy = ExponentialBackoff::with_jitter(
+            Duration::from_millis(300),
+            2.,
+            Duration::from_secs(5),
+            None,
+            0.5,
+        );
+        let mut last_retry = None;
+        for retry_num in 0..20 {
+            let delay = policy.retry(&Error::StreamEnded, last_retry).unwrap();
+            let nominal = last_retry.map_or(Duration::from_millis(300), |(_, last)| {
+                (last * 2).min(Duration::from_secs(5))
+            });
+            assert!(delay <= nominal);
+            assert!(delay >= nominal.mul_f64(0.5));
+            last_retry = Some((retry_num, nominal));
+        }
+    }
+
+    #[test]
+    fn max_retries_stops_retrying() {
+        let policy = ExponentialBackoff::new(Duration::from_millis(300), 2., Duration::from_secs(5), Some(2));
+        assert!(policy.retry(&Error::StreamEnded, Some((1, Duration::from_millis(600)))).is_some());
+        assert!(policy
+            .retry(&Error::StreamEnded, Some((2, Duration::from_millis(1200))))
+            .is_none());
+    }
+
+    #[test]
+    fn budgeted_resets_deadline_on_new_outage() {
+        let policy = Budgeted::new(Constant::new(Duration::from_millis(10), None), Duration::from_millis(20));
+        assert!(policy.retry(&Error::StreamEnded, None).is_some());
+        let first_deadline = policy.deadline.get().unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(policy.retry(&Error::StreamEnded, None).is_some());
+        assert!(policy.deadline.get().unwrap() > first_deadline);
+    }
+
+    #[test]
+    fn budgeted_gives_up_once_exceeded() {
+        let policy = Budgeted::new(Constant::new(Duration::from_millis(5), None), Duration::from_millis(10));
+        assert!(policy.retry(&Error::StreamEnded, None).is_some());
+        std::thread::sleep(Duration::from_millis(15));
+        assert!(policy
+            .retry(&Error::StreamEnded, Some((1, Duration::from_millis(5))))
+            .is_none());
+    }
+}